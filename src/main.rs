@@ -4,6 +4,240 @@ use winit::window::Window;
 use wgpu::{DeviceDescriptor, TextureFormat, PresentMode};
 use futures::executor::block_on;
 use std::borrow::BorrowMut;
+use std::fs;
+use std::path::Path;
+use wgpu::util::DeviceExt;
+use notify::Watcher;
+
+/// A single mesh vertex: position plus a per-vertex color, interleaved.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 3],
+    color: [f32; 3],
+}
+
+impl Vertex {
+    fn desc<'a>() -> wgpu::VertexBufferDescriptor<'a> {
+        wgpu::VertexBufferDescriptor {
+            stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::InputStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttributeDescriptor {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float3,
+                },
+                wgpu::VertexAttributeDescriptor {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float3,
+                },
+            ],
+        }
+    }
+}
+
+const VERTICES: &[Vertex] = &[
+    Vertex { position: [0.0, 0.5, 0.0], color: [1.0, 0.0, 0.0] },
+    Vertex { position: [-0.5, -0.5, 0.0], color: [0.0, 1.0, 0.0] },
+    Vertex { position: [0.5, -0.5, 0.0], color: [0.0, 0.0, 1.0] },
+];
+
+const INDICES: &[u16] = &[0, 1, 2];
+
+/// How a filter pass's output texture is sized relative to its input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScaleType {
+    /// Scale factor is relative to the previous pass's output size.
+    Source,
+    /// Scale factor is relative to the final viewport (swap-chain) size.
+    Viewport,
+    /// Scale factor is an absolute pixel count.
+    Absolute,
+}
+
+impl ScaleType {
+    fn parse(s: &str) -> ScaleType {
+        match s {
+            "viewport" => ScaleType::Viewport,
+            "absolute" => ScaleType::Absolute,
+            _ => ScaleType::Source,
+        }
+    }
+}
+
+/// One entry of a parsed `.slangp`-style preset: a single shader pass and
+/// how its output should be sized, filtered and wrapped.
+#[derive(Debug, Clone)]
+struct PassConfig {
+    shader_stem: String,
+    scale_type_x: ScaleType,
+    scale_x: f32,
+    scale_type_y: ScaleType,
+    scale_y: f32,
+    filter_linear: bool,
+    wrap_mode: wgpu::AddressMode,
+}
+
+fn parse_wrap_mode(s: &str) -> wgpu::AddressMode {
+    match s {
+        "repeat" => wgpu::AddressMode::Repeat,
+        "mirrored_repeat" => wgpu::AddressMode::MirrorRepeat,
+        _ => wgpu::AddressMode::ClampToEdge,
+    }
+}
+
+/// Parses a RetroArch-style `.slangp` preset describing a chain of shader
+/// passes, e.g.:
+///
+/// ```text
+/// shaders = "2"
+/// shader0 = "shaders/crt"
+/// scale_type0 = "source"
+/// scale0 = "1.0"
+/// filter_linear0 = "true"
+/// wrap_mode0 = "clamp_to_edge"
+/// shader1 = "shaders/scanlines"
+/// scale_type1 = "viewport"
+/// scale_x1 = "1.0"
+/// scale_y1 = "1.0"
+/// ```
+fn parse_preset(path: &str) -> Vec<PassConfig> {
+    let contents = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("failed to read preset {}: {}", path, e));
+
+    let mut entries = std::collections::HashMap::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim().to_string();
+            let value = value.trim().trim_matches('"').to_string();
+            entries.insert(key, value);
+        }
+    }
+
+    let pass_count: usize = entries
+        .get("shaders")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    let mut passes = Vec::with_capacity(pass_count);
+    for i in 0..pass_count {
+        let shader_stem = entries
+            .get(&format!("shader{}", i))
+            .cloned()
+            .unwrap_or_else(|| panic!("preset is missing shader{}", i));
+
+        let scale_type = entries
+            .get(&format!("scale_type{}", i))
+            .map(|s| ScaleType::parse(s))
+            .unwrap_or(ScaleType::Source);
+        let scale_type_x = entries
+            .get(&format!("scale_type_x{}", i))
+            .map(|s| ScaleType::parse(s))
+            .unwrap_or(scale_type);
+        let scale_type_y = entries
+            .get(&format!("scale_type_y{}", i))
+            .map(|s| ScaleType::parse(s))
+            .unwrap_or(scale_type);
+
+        let scale: f32 = entries
+            .get(&format!("scale{}", i))
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1.0);
+        let scale_x = entries
+            .get(&format!("scale_x{}", i))
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(scale);
+        let scale_y = entries
+            .get(&format!("scale_y{}", i))
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(scale);
+
+        let filter_linear = entries
+            .get(&format!("filter_linear{}", i))
+            .map(|s| s == "true")
+            .unwrap_or(true);
+        let wrap_mode = entries
+            .get(&format!("wrap_mode{}", i))
+            .map(|s| parse_wrap_mode(s))
+            .unwrap_or(wgpu::AddressMode::ClampToEdge);
+
+        passes.push(PassConfig {
+            shader_stem,
+            scale_type_x,
+            scale_x,
+            scale_type_y,
+            scale_y,
+            filter_linear,
+            wrap_mode,
+        });
+    }
+    passes
+}
+
+/// Uniforms handed to every filter pass shader, mirroring RetroArch's
+/// `FrameCount`/`OutputSize`/`SourceSize` conventions.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FilterUniforms {
+    output_size: [f32; 2],
+    source_size: [f32; 2],
+    frame_count: u32,
+    _padding: [u32; 3],
+}
+
+/// A single pass in the filter chain: a render pipeline that samples the
+/// previous pass's output texture and writes to this pass's own offscreen
+/// texture (or, for the last pass, directly to the swap-chain frame).
+struct FilterPass {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    output_format: TextureFormat,
+    /// `None` for the last pass in the chain, which renders straight into
+    /// the swap-chain frame instead of an offscreen texture.
+    output: Option<(wgpu::Texture, wgpu::TextureView)>,
+    output_size: (u32, u32),
+}
+
+fn compute_pass_size(
+    scale_type: ScaleType,
+    scale: f32,
+    source_component: u32,
+    viewport_component: u32,
+) -> u32 {
+    match scale_type {
+        ScaleType::Source => (source_component.max(1) as f32 * scale).round().max(1.0) as u32,
+        ScaleType::Viewport => (viewport_component.max(1) as f32 * scale).round().max(1.0) as u32,
+        ScaleType::Absolute => scale.round().max(1.0) as u32,
+    }
+}
+
+/// Picks which wgpu backends to request, honoring a `WGPU_BACKEND` override
+/// (`vulkan`, `metal`, `dx12`, `dx11`, or `gl`) so the example isn't pinned
+/// to Vulkan on platforms that don't support it.
+fn backend_bits_from_env() -> wgpu::BackendBit {
+    match std::env::var("WGPU_BACKEND") {
+        Ok(val) => match val.to_lowercase().as_str() {
+            "vulkan" => wgpu::BackendBit::VULKAN,
+            "metal" => wgpu::BackendBit::METAL,
+            "dx12" => wgpu::BackendBit::DX12,
+            "dx11" => wgpu::BackendBit::DX11,
+            "gl" => wgpu::BackendBit::GL,
+            other => {
+                println!("unknown WGPU_BACKEND {:?}, requesting all primary backends", other);
+                wgpu::BackendBit::PRIMARY
+            }
+        },
+        Err(_) => wgpu::BackendBit::PRIMARY,
+    }
+}
 
 struct State {
     surface: wgpu::Surface,
@@ -14,19 +248,243 @@ struct State {
     size: winit::dpi::PhysicalSize<u32>,
     clear_color: wgpu::Color,
     render_pipeline: wgpu::RenderPipeline,
+    scene_pipeline_layout: wgpu::PipelineLayout,
+    compiler: shaderc::Compiler,
+    shader_watcher: notify::RecommendedWatcher,
+    shader_watch_rx: std::sync::mpsc::Receiver<notify::DebouncedEvent>,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+    scene_texture: wgpu::Texture,
+    scene_view: wgpu::TextureView,
+    scene_target_format: TextureFormat,
+    pass_configs: Vec<PassConfig>,
+    filter_passes: Vec<FilterPass>,
+    frame_count: u32,
 }
 
 impl State {
+    fn create_offscreen_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        label: &str,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    /// Builds the fullscreen-triangle pipeline and offscreen target for one
+    /// filter pass, sourcing its shader from `<stem>.vert`/`<stem>.frag`.
+    fn build_filter_pass(
+        device: &wgpu::Device,
+        compiler: &mut shaderc::Compiler,
+        config: &PassConfig,
+        source_size: (u32, u32),
+        viewport_size: (u32, u32),
+        swap_chain_format: TextureFormat,
+        is_last: bool,
+    ) -> FilterPass {
+        let width = compute_pass_size(config.scale_type_x, config.scale_x, source_size.0, viewport_size.0);
+        let height = compute_pass_size(config.scale_type_y, config.scale_y, source_size.1, viewport_size.1);
+
+        let vert_path = format!("{}.vert", config.shader_stem);
+        let frag_path = format!("{}.frag", config.shader_stem);
+        let vertex_glsl = fs::read_to_string(&vert_path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", vert_path, e));
+        let fragment_glsl = fs::read_to_string(&frag_path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", frag_path, e));
+
+        let vs_spirv = compiler
+            .compile_into_spirv(&vertex_glsl, shaderc::ShaderKind::Vertex, &vert_path, "main", None)
+            .unwrap();
+        let fs_spirv = compiler
+            .compile_into_spirv(&fragment_glsl, shaderc::ShaderKind::Fragment, &frag_path, "main", None)
+            .unwrap();
+
+        let vs_module = device.create_shader_module(wgpu::util::make_spirv(vs_spirv.as_binary_u8()));
+        let fs_module = device.create_shader_module(wgpu::util::make_spirv(fs_spirv.as_binary_u8()));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("filter pass bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry::new(
+                    0,
+                    wgpu::ShaderStage::FRAGMENT,
+                    wgpu::BindingType::SampledTexture {
+                        dimension: wgpu::TextureViewDimension::D2,
+                        component_type: wgpu::TextureComponentType::Float,
+                        multisampled: false,
+                    },
+                ),
+                wgpu::BindGroupLayoutEntry::new(
+                    1,
+                    wgpu::ShaderStage::FRAGMENT,
+                    wgpu::BindingType::Sampler { comparison: false },
+                ),
+                wgpu::BindGroupLayoutEntry::new(
+                    2,
+                    wgpu::ShaderStage::FRAGMENT,
+                    wgpu::BindingType::UniformBuffer {
+                        dynamic: false,
+                        min_binding_size: None,
+                    },
+                ),
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("filter pass pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let output_format = if is_last { swap_chain_format } else { TextureFormat::Rgba8UnormSrgb };
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("filter pass pipeline"),
+            layout: Some(&layout),
+            vertex_stage: wgpu::ProgrammableStageDescriptor { module: &vs_module, entry_point: "main" },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor { module: &fs_module, entry_point: "main" }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                front_face: Default::default(),
+                cull_mode: Default::default(),
+                clamp_depth: false,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+            }),
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format: output_format,
+                alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                color_blend: wgpu::BlendDescriptor::REPLACE,
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            depth_stencil_state: None,
+            vertex_state: wgpu::VertexStateDescriptor { index_format: wgpu::IndexFormat::Uint16, vertex_buffers: &[] },
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        });
+
+        let filter_mode = if config.filter_linear { wgpu::FilterMode::Linear } else { wgpu::FilterMode::Nearest };
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("filter pass sampler"),
+            address_mode_u: config.wrap_mode,
+            address_mode_v: config.wrap_mode,
+            address_mode_w: config.wrap_mode,
+            mag_filter: filter_mode,
+            min_filter: filter_mode,
+            mipmap_filter: filter_mode,
+            ..Default::default()
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("filter pass uniform buffer"),
+            size: std::mem::size_of::<FilterUniforms>() as u64,
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // The last pass writes straight into the swap-chain frame (see
+        // `render`), so it has no offscreen output texture of its own.
+        let output = if is_last {
+            None
+        } else {
+            Some(Self::create_offscreen_texture(device, width, height, output_format, "filter pass output texture"))
+        };
+
+        FilterPass {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            uniform_buffer,
+            output_format,
+            output,
+            output_size: (width, height),
+        }
+    }
+
+    /// Builds the whole filter chain for the current viewport size, feeding
+    /// each pass's output size forward as the next pass's source size.
+    fn build_filter_passes(
+        device: &wgpu::Device,
+        compiler: &mut shaderc::Compiler,
+        pass_configs: &[PassConfig],
+        viewport_size: (u32, u32),
+        swap_chain_format: TextureFormat,
+    ) -> Vec<FilterPass> {
+        let mut filter_passes = Vec::with_capacity(pass_configs.len());
+        let mut source_size = viewport_size;
+        for (i, config) in pass_configs.iter().enumerate() {
+            let is_last = i == pass_configs.len() - 1;
+            let pass = Self::build_filter_pass(
+                device,
+                compiler,
+                config,
+                source_size,
+                viewport_size,
+                swap_chain_format,
+                is_last,
+            );
+            source_size = pass.output_size;
+            filter_passes.push(pass);
+        }
+        filter_passes
+    }
+
+    /// Builds a bind group for `pass` that samples `input_view` and binds the
+    /// pass's uniform buffer; the buffer's contents are written by `render`.
+    fn make_pass_bind_group(
+        device: &wgpu::Device,
+        pass: &FilterPass,
+        input_view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("filter pass bind group"),
+            layout: &pass.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(input_view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&pass.sampler) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Buffer(pass.uniform_buffer.slice(..)) },
+            ],
+        })
+    }
+
     async fn new(window: &Window) -> Self {
         let size = window.inner_size();
 
-        let instance = wgpu::Instance::new(wgpu::BackendBit::VULKAN);
+        let instance = wgpu::Instance::new(backend_bits_from_env());
         let surface = unsafe { instance.create_surface(window) };
 
-        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::Default,
+        let adapter = match instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
             compatible_surface: Some(&surface),
-        }).await.unwrap();
+        }).await {
+            Some(adapter) => adapter,
+            None => {
+                println!("no high-performance adapter available, falling back to low-power/software");
+                instance.request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::LowPower,
+                    compatible_surface: Some(&surface),
+                }).await.expect("no suitable graphics adapter found")
+            }
+        };
 
         let (device, queue) = adapter.request_device(
             &DeviceDescriptor {
@@ -37,10 +495,13 @@ impl State {
             None,
         ).await.unwrap();
 
+        let swap_chain_format = surface
+            .get_preferred_format(&adapter)
+            .unwrap_or(TextureFormat::Bgra8UnormSrgb);
 
         let swap_chain_desc = wgpu::SwapChainDescriptor {
             usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-            format: TextureFormat::Bgra8UnormSrgb,
+            format: swap_chain_format,
             width: size.width,
             height: size.height,
             present_mode: PresentMode::Fifo,
@@ -64,15 +525,28 @@ impl State {
         let vs_module = device.create_shader_module(wgpu::util::make_spirv(vs_spirv.as_binary_u8()));
         let fs_module = device.create_shader_module(wgpu::util::make_spirv(fs_spirv.as_binary_u8()));
 
-        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        let scene_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("a pipeline layout of mine"),
             bind_group_layouts: &[],
             push_constant_ranges: &[],
         });
 
+        let pass_configs = if Path::new("preset.slangp").exists() {
+            parse_preset("preset.slangp")
+        } else {
+            Vec::new()
+        };
+
+        // The scene is rendered offscreen so that it can feed the preset's
+        // filter chain instead of going straight to the swap-chain. When
+        // there's no filter chain, the scene pipeline draws straight into
+        // the swap-chain frame instead, so its color target must match the
+        // swap chain's format rather than the offscreen one in that case.
+        let scene_format = TextureFormat::Rgba8UnormSrgb;
+        let scene_target_format = if pass_configs.is_empty() { swap_chain_desc.format } else { scene_format };
         let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("my pipeline"),
-            layout: Some(&layout),
+            layout: Some(&scene_pipeline_layout),
             vertex_stage: wgpu::ProgrammableStageDescriptor { module: &vs_module, entry_point: "main" },
             fragment_stage: Some(wgpu::ProgrammableStageDescriptor { module: &fs_module, entry_point: "main" }),
             rasterization_state: Some(wgpu::RasterizationStateDescriptor {
@@ -85,18 +559,54 @@ impl State {
             }),
             primitive_topology: wgpu::PrimitiveTopology::TriangleList,
             color_states: &[wgpu::ColorStateDescriptor {
-                format: swap_chain_desc.format,
+                format: scene_target_format,
                 alpha_blend: wgpu::BlendDescriptor::REPLACE,
                 color_blend: wgpu::BlendDescriptor::REPLACE,
                 write_mask: wgpu::ColorWrite::ALL,
             }],
             depth_stencil_state: None,
-            vertex_state: wgpu::VertexStateDescriptor { index_format: wgpu::IndexFormat::Uint16, vertex_buffers: &[] },
+            vertex_state: wgpu::VertexStateDescriptor {
+                index_format: wgpu::IndexFormat::Uint16,
+                vertex_buffers: &[Vertex::desc()],
+            },
             sample_count: 1,
             sample_mask: !0,
             alpha_to_coverage_enabled: false,
         });
 
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Vertex Buffer"),
+            contents: bytemuck::cast_slice(VERTICES),
+            usage: wgpu::BufferUsage::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Index Buffer"),
+            contents: bytemuck::cast_slice(INDICES),
+            usage: wgpu::BufferUsage::INDEX,
+        });
+        let num_indices = INDICES.len() as u32;
+
+        let (scene_texture, scene_view) =
+            Self::create_offscreen_texture(&device, size.width, size.height, scene_format, "scene texture");
+
+        let filter_passes = Self::build_filter_passes(
+            &device,
+            &mut compiler,
+            &pass_configs,
+            (size.width, size.height),
+            swap_chain_desc.format,
+        );
+
+        let (shader_watch_tx, shader_watch_rx) = std::sync::mpsc::channel();
+        let mut shader_watcher: notify::RecommendedWatcher =
+            notify::Watcher::new(shader_watch_tx, std::time::Duration::from_millis(200))
+                .expect("failed to set up shader file watcher");
+        for path in &["src/shader.vert", "src/shader.frag"] {
+            if let Err(e) = shader_watcher.watch(path, notify::RecursiveMode::NonRecursive) {
+                println!("shader hot-reload: not watching {}: {}", path, e);
+            }
+        }
+
         Self {
             surface,
             device,
@@ -105,43 +615,231 @@ impl State {
             swap_chain_desc,
             size,
             clear_color: Default::default(),
-            render_pipeline
+            render_pipeline,
+            scene_pipeline_layout,
+            compiler,
+            shader_watcher,
+            shader_watch_rx,
+            vertex_buffer,
+            index_buffer,
+            num_indices,
+            scene_texture,
+            scene_view,
+            scene_target_format,
+            pass_configs,
+            filter_passes,
+            frame_count: 0,
         }
     }
 
     fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        if new_size.width == 0 || new_size.height == 0 {
+            // Minimizing reports a 0x0 size; wgpu can't create a swap chain
+            // or texture for that, so just remember the size and bail until
+            // the window is restored.
+            self.size = new_size;
+            return;
+        }
+        // render()'s SwapChainError recovery calls this with the current
+        // size unchanged, precisely to force the swap chain below to be
+        // recreated — so that always has to run. Only the filter-chain
+        // texture rebuild further down is skippable when nothing actually
+        // changed size.
+        let size_changed = new_size != self.size;
+
         println!("resize to {:?}", new_size);
         self.size = new_size;
         self.swap_chain_desc.height = new_size.height;
         self.swap_chain_desc.width = new_size.width;
         self.swap_chain = self.device.create_swap_chain(&self.surface, &self.swap_chain_desc);
+
+        let (scene_texture, scene_view) = Self::create_offscreen_texture(
+            &self.device,
+            new_size.width,
+            new_size.height,
+            TextureFormat::Rgba8UnormSrgb,
+            "scene texture",
+        );
+        self.scene_texture = scene_texture;
+        self.scene_view = scene_view;
+
+        if !size_changed {
+            return;
+        }
+
+        // Viewport-relative passes in the filter chain are sized off the
+        // swap-chain size, so their offscreen targets need to be recreated
+        // at the new size. This only resizes the output textures — the
+        // pipeline, shaders, sampler and bind group layout it was built
+        // with in `build_filter_pass` don't depend on size and stay as-is.
+        // The last pass has no offscreen output (it renders straight into
+        // the swap-chain frame), so there's nothing to resize for it.
+        let mut source_size = (new_size.width, new_size.height);
+        for (pass, config) in self.filter_passes.iter_mut().zip(self.pass_configs.iter()) {
+            let width = compute_pass_size(config.scale_type_x, config.scale_x, source_size.0, new_size.width);
+            let height = compute_pass_size(config.scale_type_y, config.scale_y, source_size.1, new_size.height);
+            if pass.output.is_some() {
+                pass.output = Some(Self::create_offscreen_texture(&self.device, width, height, pass.output_format, "filter pass output texture"));
+            }
+            pass.output_size = (width, height);
+            source_size = pass.output_size;
+        }
+    }
+
+    /// Drains pending file-watch events for `shader.vert`/`shader.frag` and,
+    /// if either changed, attempts to recompile and rebuild the scene
+    /// pipeline. A bad shader is logged and the last-good pipeline stays
+    /// bound, so a typo doesn't take the app down.
+    fn poll_shader_reload(&mut self) {
+        let mut changed = false;
+        while let Ok(_event) = self.shader_watch_rx.try_recv() {
+            changed = true;
+        }
+        if changed {
+            self.reload_scene_shaders();
+        }
+    }
+
+    fn reload_scene_shaders(&mut self) {
+        let vertex_glsl = match fs::read_to_string("src/shader.vert") {
+            Ok(s) => s,
+            Err(e) => { println!("shader hot-reload: failed to read src/shader.vert: {}", e); return; }
+        };
+        let fragment_glsl = match fs::read_to_string("src/shader.frag") {
+            Ok(s) => s,
+            Err(e) => { println!("shader hot-reload: failed to read src/shader.frag: {}", e); return; }
+        };
+
+        let vs_spirv = match self.compiler.compile_into_spirv(
+            &vertex_glsl, shaderc::ShaderKind::Vertex, "shader.vert", "main", None,
+        ) {
+            Ok(artifact) => artifact,
+            Err(e) => { println!("shader hot-reload: shader.vert failed to compile: {}", e); return; }
+        };
+        let fs_spirv = match self.compiler.compile_into_spirv(
+            &fragment_glsl, shaderc::ShaderKind::Fragment, "shader.frag", "main", None,
+        ) {
+            Ok(artifact) => artifact,
+            Err(e) => { println!("shader hot-reload: shader.frag failed to compile: {}", e); return; }
+        };
+
+        let vs_module = self.device.create_shader_module(wgpu::util::make_spirv(vs_spirv.as_binary_u8()));
+        let fs_module = self.device.create_shader_module(wgpu::util::make_spirv(fs_spirv.as_binary_u8()));
+
+        let render_pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("my pipeline"),
+            layout: Some(&self.scene_pipeline_layout),
+            vertex_stage: wgpu::ProgrammableStageDescriptor { module: &vs_module, entry_point: "main" },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor { module: &fs_module, entry_point: "main" }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                front_face: Default::default(),
+                cull_mode: Default::default(),
+                clamp_depth: false,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+            }),
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format: self.scene_target_format,
+                alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                color_blend: wgpu::BlendDescriptor::REPLACE,
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            depth_stencil_state: None,
+            vertex_state: wgpu::VertexStateDescriptor {
+                index_format: wgpu::IndexFormat::Uint16,
+                vertex_buffers: &[Vertex::desc()],
+            },
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        });
+
+        self.render_pipeline = render_pipeline;
+        println!("shader hot-reload: rebuilt scene pipeline");
     }
 
-    fn render(&mut self) {
-        let frame = self.swap_chain.get_current_frame()
-            .unwrap()
-            .output;
+    /// Renders the scene into the offscreen scene texture, then runs it
+    /// through the preset's filter chain, with the last pass writing
+    /// straight into the swap-chain frame. With no filter chain the scene
+    /// is drawn straight into the swap-chain frame.
+    ///
+    /// Returns the `SwapChainError` from `get_current_frame` instead of
+    /// panicking on it, so callers can recover from the errors that resize
+    /// and minimize routinely trigger.
+    fn render(&mut self) -> Result<(), wgpu::SwapChainError> {
+        self.poll_shader_reload();
+
+        let frame = self.swap_chain.get_current_frame()?.output;
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder")
         });
 
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            color_attachments: &[
-                wgpu::RenderPassColorAttachmentDescriptor {
-                    attachment: &frame.view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(self.clear_color),
-                        store: true,
-                    },
-                }
-            ],
-            depth_stencil_attachment: None,
-        });
-        render_pass.set_pipeline(&self.render_pipeline);
-        render_pass.draw(0..3, 0..1);
-        drop(render_pass);
+        {
+            let scene_attachment = if self.filter_passes.is_empty() { &frame.view } else { &self.scene_view };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[
+                    wgpu::RenderPassColorAttachmentDescriptor {
+                        attachment: scene_attachment,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(self.clear_color),
+                            store: true,
+                        },
+                    }
+                ],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(self.index_buffer.slice(..));
+            render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+        }
+
+        let mut input_view = &self.scene_view;
+        let mut source_size = (self.size.width, self.size.height);
+        let last_index = self.filter_passes.len().checked_sub(1);
+        for (i, pass) in self.filter_passes.iter().enumerate() {
+            let uniforms = FilterUniforms {
+                output_size: [pass.output_size.0 as f32, pass.output_size.1 as f32],
+                source_size: [source_size.0 as f32, source_size.1 as f32],
+                frame_count: self.frame_count,
+                _padding: [0; 3],
+            };
+            self.queue.write_buffer(&pass.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+
+            let bind_group = Self::make_pass_bind_group(&self.device, pass, input_view);
+            let is_last = Some(i) == last_index;
+            let attachment = match &pass.output {
+                Some((_, view)) if !is_last => view,
+                _ => &frame.view,
+            };
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[
+                    wgpu::RenderPassColorAttachmentDescriptor {
+                        attachment,
+                        resolve_target: None,
+                        ops: wgpu::Operations { load: wgpu::LoadOp::Clear(self.clear_color), store: true },
+                    }
+                ],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&pass.pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+            drop(render_pass);
+
+            if let Some((_, view)) = &pass.output {
+                input_view = view;
+            }
+            source_size = pass.output_size;
+        }
+
         self.queue.submit(std::iter::once(encoder.finish()));
+        self.frame_count = self.frame_count.wrapping_add(1);
+        Ok(())
     }
 }
 
@@ -180,7 +878,19 @@ fn main() {
                 }
             }
             Event::RedrawRequested(_) => {
-                state.render();
+                match state.render() {
+                    Ok(_) => {}
+                    // Lost/outdated surfaces happen on resize and on waking
+                    // from minimize; recreating the swap chain recovers.
+                    Err(wgpu::SwapChainError::Lost) | Err(wgpu::SwapChainError::Outdated) => state.resize(state.size),
+                    // The GPU didn't produce a frame in time; just try again
+                    // next redraw instead of treating it as an error.
+                    Err(wgpu::SwapChainError::Timeout) => {}
+                    Err(wgpu::SwapChainError::OutOfMemory) => {
+                        eprintln!("swap chain out of memory, exiting");
+                        *control_flow = ControlFlow::Exit;
+                    }
+                }
             }
             Event::MainEventsCleared => {
                 window.request_redraw();